@@ -0,0 +1,99 @@
+use std::str::FromStr;
+
+use anyhow::{Result, anyhow};
+use clap::Parser;
+use hex_color::HexColor;
+
+use crate::config::Config;
+
+const KATAKANA: &str = "ﾊﾐﾋｰｳｼﾅﾓﾆｻﾜﾂｵﾘｱﾎﾃﾏｹﾒｴｶｷﾑﾕﾗｾﾈｽﾀﾇﾍｦｲｸｺｿﾁﾄﾉﾌﾔﾖﾙﾚﾛﾝ¦*+-,.;";
+const ASCII: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789¦*+-,.;";
+
+/// Glyph pool selected on the command line.
+#[derive(Clone, Debug)]
+pub enum Charset {
+    Ascii,
+    Katakana,
+    Custom(String),
+}
+
+impl Charset {
+    fn glyphs(&self) -> String {
+        match self {
+            Charset::Ascii => ASCII.to_string(),
+            Charset::Katakana => KATAKANA.to_string(),
+            Charset::Custom(s) => s.clone(),
+        }
+    }
+}
+
+impl FromStr for Charset {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "ascii" => Ok(Charset::Ascii),
+            "katakana" => Ok(Charset::Katakana),
+            other => match other.strip_prefix("custom:") {
+                Some(custom) if !custom.is_empty() => Ok(Charset::Custom(custom.to_string())),
+                _ => Err(anyhow!("charset must be ascii, katakana or custom:<string>")),
+            },
+        }
+    }
+}
+
+/// Parse a frames-per-second value, rejecting non-positive rates.
+fn parse_fps(s: &str) -> std::result::Result<f64, String> {
+    let fps: f64 = s.parse().map_err(|_| format!("invalid number: {s}"))?;
+    if fps > 0.0 {
+        Ok(fps)
+    } else {
+        Err("fps must be greater than 0".to_string())
+    }
+}
+
+/// Terminal digital-rain effect.
+#[derive(Parser, Debug)]
+#[command(about, long_about = None)]
+pub struct Cli {
+    /// Seed the RNG for reproducible runs (defaults to the system clock).
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Delay between frames in milliseconds.
+    #[arg(long, conflicts_with = "fps")]
+    pub delay: Option<u64>,
+
+    /// Frames per second (converted to a frame delay).
+    #[arg(long, conflicts_with = "delay", value_parser = parse_fps)]
+    pub fps: Option<f64>,
+
+    /// Palette color (repeatable), e.g. --color '#00ff2b'. The given colors
+    /// replace the whole palette and select the `custom` scheme.
+    #[arg(long)]
+    pub color: Vec<HexColor>,
+
+    /// Glyph pool: ascii, katakana or custom:<string>.
+    #[arg(long)]
+    pub charset: Option<Charset>,
+}
+
+impl Cli {
+    /// Merge the parsed flags onto a config, letting the command line win.
+    pub fn apply(&self, config: &mut Config) {
+        if let Some(delay) = self.delay {
+            config.frame_delay_ms = delay;
+        } else if let Some(fps) = self.fps {
+            config.frame_delay_ms = (1000.0 / fps).round() as u64;
+        }
+
+        if !self.color.is_empty() {
+            config.colors = self.color.clone();
+            config.scheme = "custom".to_string();
+        }
+
+        if let Some(charset) = &self.charset {
+            config.glyphs = charset.glyphs();
+        }
+    }
+}
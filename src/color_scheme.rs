@@ -0,0 +1,94 @@
+use anyhow::{Result, bail};
+use hex_color::HexColor;
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::config::Config;
+
+/// How a color is picked from the palette for each spawned glyph.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SelectionMode {
+    /// Pick any palette entry at random whenever a glyph is written.
+    #[default]
+    RandomPerGlyph,
+    /// Columns walk through the palette in order, wrapping around (column `i`
+    /// uses entry `i % len`).
+    SequentialPerColumn,
+    /// Each column is assigned one random palette entry for its lifetime.
+    FixedPerColumn,
+}
+
+/// A named palette together with a selection mode.
+#[derive(Clone, Debug)]
+pub struct ColorScheme {
+    pub colors: Vec<HexColor>,
+    pub mode: SelectionMode,
+}
+
+impl ColorScheme {
+    /// Look up a built-in palette by name, or `None` if unknown.
+    pub fn builtin(name: &str) -> Option<Vec<HexColor>> {
+        let colors = match name {
+            "matrix-green" => vec![
+                HexColor::rgb(0, 255, 43),
+                HexColor::rgb(0, 204, 34),
+                HexColor::rgb(0, 160, 30),
+            ],
+            "solarized" => vec![
+                HexColor::rgb(133, 153, 0),
+                HexColor::rgb(42, 161, 152),
+                HexColor::rgb(38, 139, 210),
+                HexColor::rgb(181, 137, 0),
+            ],
+            "tomorrow-night-bright" => vec![
+                HexColor::rgb(185, 202, 74),
+                HexColor::rgb(112, 192, 177),
+                HexColor::rgb(122, 166, 218),
+                HexColor::rgb(210, 123, 83),
+            ],
+            _ => return None,
+        };
+        Some(colors)
+    }
+
+    /// Built-in scheme names, in cycle order (used by interactive switching).
+    pub fn names() -> &'static [&'static str] {
+        &["matrix-green", "solarized", "tomorrow-night-bright"]
+    }
+
+    /// Resolve the scheme named in the config, falling back to the config's own
+    /// `colors` list for the special name `custom`.
+    pub fn resolve(config: &Config) -> Result<Self> {
+        let colors = if config.scheme == "custom" {
+            config.colors.clone()
+        } else {
+            Self::builtin(&config.scheme).unwrap_or_else(|| config.colors.clone())
+        };
+        if colors.is_empty() {
+            bail!("color scheme {:?} has an empty palette", config.scheme);
+        }
+        Ok(Self {
+            colors,
+            mode: config.mode,
+        })
+    }
+
+    /// Color assigned to a column at construction. Only meaningful for the
+    /// per-column modes; `RandomPerGlyph` recomputes on every glyph.
+    pub fn column_color<R: Rng>(&self, index: usize, rand: &mut R) -> HexColor {
+        match self.mode {
+            SelectionMode::SequentialPerColumn => self.colors[index % self.colors.len()],
+            SelectionMode::FixedPerColumn => self.colors[rand.random_range(0..self.colors.len())],
+            SelectionMode::RandomPerGlyph => self.colors[0],
+        }
+    }
+
+    /// Color for a freshly written glyph, given its column's assigned color.
+    pub fn glyph_color<R: Rng>(&self, assigned: HexColor, rand: &mut R) -> HexColor {
+        match self.mode {
+            SelectionMode::RandomPerGlyph => self.colors[rand.random_range(0..self.colors.len())],
+            _ => assigned,
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{Context, Result, bail};
+use hex_color::HexColor;
+use serde::Deserialize;
+
+use crate::color_scheme::SelectionMode;
+
+/// User-tunable settings for the rain, loaded from a TOML file at startup so
+/// the palette, speed and glyph pool can be themed without recompiling.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Delay between frames in milliseconds (was the hard-coded 80).
+    pub frame_delay_ms: u64,
+    /// Factor the saturation is multiplied by on every fade step.
+    pub fade_saturation: f32,
+    /// Factor the lightness is multiplied by on every fade step.
+    pub fade_lightness: f32,
+    /// Pool of characters glyphs are picked from.
+    pub glyphs: String,
+    /// Colors a freshly written glyph can take when `scheme` is `custom`.
+    pub colors: Vec<HexColor>,
+    /// Named color scheme: a built-in (`matrix-green`, `solarized`,
+    /// `tomorrow-night-bright`) or `custom` to use `colors`.
+    pub scheme: String,
+    /// How colors are chosen from the scheme's palette.
+    pub mode: SelectionMode,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            frame_delay_ms: 80,
+            fade_saturation: 0.9,
+            fade_lightness: 0.93,
+            glyphs: "ﾊﾐﾋｰｳｼﾅﾓﾆｻﾜﾂｵﾘｱﾎﾃﾏｹﾒｴｶｷﾑﾕﾗｾﾈｽﾀﾇﾍｦｲｸｺｿﾁﾄﾉﾌﾔﾖﾙﾚﾛﾝ¦*+-,.;"
+                .to_string(),
+            colors: vec![HexColor::rgb(0, 150, 255), HexColor::rgb(0, 255, 43)],
+            scheme: "matrix-green".to_string(),
+            mode: SelectionMode::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Default location of the config file: `~/.config/matrixrain/config.toml`.
+    pub fn default_path() -> Option<PathBuf> {
+        let home = std::env::var_os("HOME")?;
+        Some(
+            PathBuf::from(home)
+                .join(".config")
+                .join("matrixrain")
+                .join("config.toml"),
+        )
+    }
+
+    /// Load the config from the default path, falling back to the built-in
+    /// defaults when the file is absent.
+    pub fn load() -> Result<Self> {
+        match Self::default_path() {
+            Some(path) if path.exists() => Self::from_file(path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Parse a config from a specific TOML file.
+    pub fn from_file(path: PathBuf) -> Result<Self> {
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("read config file {}", path.display()))?;
+        let config: Config = toml::from_str(&contents).context("parse config file as TOML")?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Reject configs that would panic the animation, such as an empty glyph
+    /// pool (`Glyph::new_random` indexes into it on every spawn).
+    pub fn validate(&self) -> Result<()> {
+        if self.glyphs.is_empty() {
+            bail!("config `glyphs` pool must not be empty");
+        }
+        Ok(())
+    }
+}
@@ -1,40 +1,45 @@
+mod cli;
+mod color_scheme;
+mod config;
+
 use std::{
     io::Write,
     time::{Duration, SystemTime},
 };
 
 use anyhow::{Context, Result};
-use crossterm::{cursor, queue, style, terminal};
+use crossterm::event::{self, Event, KeyCode};
+use crossterm::{cursor, execute, queue, style, terminal};
 use hex_color::HexColor;
-use palette::RgbHue;
 use palette::{FromColor, Hsl, Srgb};
-use rand::{Rng, RngCore};
+use rand::Rng;
 use rand_xoshiro::Xoshiro256PlusPlus;
 use rand_xoshiro::rand_core::SeedableRng;
 
-#[derive(Clone)]
+use clap::Parser;
+
+use crate::cli::Cli;
+use crate::color_scheme::ColorScheme;
+use crate::config::Config;
+
+#[derive(Clone, PartialEq)]
 struct Glyph {
     character: char,
     color: HexColor,
 }
 
 impl Glyph {
-    fn new(character: char, color: HexColor) -> Self {
-        Self { character, color }
-    }
-
-    fn new_random<R: Rng>(rand: &mut R, color: HexColor) -> Self {
-        let characters = "ﾊﾐﾋｰｳｼﾅﾓﾆｻﾜﾂｵﾘｱﾎﾃﾏｹﾒｴｶｷﾑﾕﾗｾﾈｽﾀﾇﾍｦｲｸｺｿﾁﾄﾉﾌﾔﾖﾙﾚﾛﾝ¦*+-,.;";
+    fn new_random<R: Rng>(rand: &mut R, color: HexColor, glyphs: &str) -> Self {
         Self {
-            character: characters
+            character: glyphs
                 .chars()
-                .nth(rand.random_range(0..characters.chars().count()))
+                .nth(rand.random_range(0..glyphs.chars().count()))
                 .unwrap(),
             color,
         }
     }
 
-    fn fade_color(&mut self) {
+    fn fade_color(&mut self, fade_saturation: f32, fade_lightness: f32) {
         let rgb = Srgb::new(
             self.color.r as f32 / 255.0,
             self.color.g as f32 / 255.0,
@@ -43,8 +48,8 @@ impl Glyph {
 
         let mut hsl: Hsl = Hsl::from_color(rgb);
 
-        hsl.saturation = (hsl.saturation * 0.9).clamp(0.0, 1.0);
-        hsl.lightness = (hsl.lightness * 0.93).clamp(0.0, 1.0);
+        hsl.saturation = (hsl.saturation * fade_saturation).clamp(0.0, 1.0);
+        hsl.lightness = (hsl.lightness * fade_lightness).clamp(0.0, 1.0);
 
         let new_rgb: Srgb = Srgb::from_color(hsl);
 
@@ -60,72 +65,77 @@ impl Glyph {
             color: HexColor::rgb(0, 0, 0),
         }
     }
-
-    fn render<W: Write>(&self, out: &mut W) -> Result<()> {
-        queue!(
-            out,
-            style::SetBackgroundColor(style::Color::Rgb {
-                r: (0),
-                g: (0),
-                b: (0)
-            })
-        )?;
-        queue!(
-            out,
-            style::SetForegroundColor(style::Color::Rgb {
-                r: self.color.r,
-                g: self.color.g,
-                b: self.color.b
-            })
-        )?;
-        queue!(out, style::Print(self.character.to_string()))
-            .context("write glyph to unicode chars")?;
-
-        Ok(())
-    }
 }
+/// Bright near-white color of the leading glyph at the wavefront.
+const HEAD_COLOR: HexColor = HexColor::rgb(215, 255, 215);
+
 #[derive(Clone)]
 struct Column {
     height: u16,
     base_color: HexColor,
     glyphs: Vec<Glyph>,
     active_index: usize,
+    /// Advance the head only once every `speed` ticks.
+    speed: u8,
+    /// Ticks elapsed since the head last advanced.
+    tick_accum: u8,
+    /// Ticks to wait before this column starts (again).
+    respawn: u16,
 }
 
 impl Column {
-    fn new(height: u16, base_color: HexColor) -> Self {
+    fn new<R: Rng>(height: u16, index: usize, scheme: &ColorScheme, rand: &mut R) -> Self {
         Self {
             height,
-            base_color,
+            base_color: scheme.column_color(index, rand),
             glyphs: vec![Glyph::empty(); height as usize],
             active_index: 0,
+            speed: rand.random_range(1..=4),
+            tick_accum: 0,
+            // stagger the initial start so columns don't march in lockstep
+            respawn: rand.random_range(0..=20),
         }
     }
 
-    fn render<W: Write>(&self, out: &mut W, y: u16) -> Result<()> {
-        self.glyphs[y as usize].render(out);
-        Ok(())
-    }
-
-    fn step<R: Rng>(&mut self, rand: &mut R) {
-        if self.active_index == 0 && rand.random::<f32>() > 0.1 {
+    fn step<R: Rng>(&mut self, rand: &mut R, config: &Config, scheme: &ColorScheme) {
+        if self.respawn > 0 {
+            // keep dimming the trail while the column waits to restart, so no
+            // stale bright glyph is left frozen at the bottom.
+            for glyph in &mut self.glyphs {
+                glyph.fade_color(config.fade_saturation, config.fade_lightness);
+            }
+            self.respawn -= 1;
             return;
         }
 
-        for glyph in &mut self.glyphs {
-            glyph.fade_color();
+        self.tick_accum += 1;
+        if self.tick_accum < self.speed {
+            return;
         }
+        self.tick_accum = 0;
 
-        let base_color = HexColor::rgb(0, 150, 255);
-        let base_color2 = HexColor::rgb(0, 255, 43);
-        let chosen = choose_random(base_color, base_color2);
+        let chosen = scheme.glyph_color(self.base_color, rand);
 
-        // just put a single color here instead of randoming between blue and green :)
+        // the glyph written on the previous advance is no longer the head; drop it from the
+        // bright head color to its resting hue before the whole column fades.
+        if self.active_index > 0 {
+            self.glyphs[self.active_index - 1].color = chosen;
+        }
+        for glyph in &mut self.glyphs {
+            glyph.fade_color(config.fade_saturation, config.fade_lightness);
+        }
 
-        self.glyphs[self.active_index] = Glyph::new_random(rand, chosen);
+        // write the new bright head at the wavefront
+        self.glyphs[self.active_index] = Glyph::new_random(rand, HEAD_COLOR, &config.glyphs);
         self.active_index += 1;
         if self.active_index >= self.height as usize {
+            // wrapped: demote the head just written at the bottom row before it
+            // would otherwise be stranded at HEAD_COLOR, then pick a fresh speed
+            // and wait a random spell before falling again.
+            self.glyphs[self.active_index - 1].color = chosen;
             self.active_index = 0;
+            self.speed = rand.random_range(1..=4);
+            self.respawn = rand.random_range(0..=30);
         }
     }
 }
@@ -133,65 +143,189 @@ impl Column {
 struct MatrixWaterFall {
     width: u16,
     height: u16,
-    base_color: HexColor,
     columns: Vec<Column>,
+    /// Last-rendered grid, diffed against on each frame so only changed cells
+    /// are re-emitted. Indexed `front[y][x]`.
+    front: Vec<Vec<Glyph>>,
 }
 impl MatrixWaterFall {
-    fn new(width: u16, height: u16, base_color: HexColor) -> Self {
+    fn new<R: Rng>(width: u16, height: u16, scheme: &ColorScheme, rand: &mut R) -> Self {
+        let columns = (0..width)
+            .map(|i| Column::new(height, i as usize, scheme, rand))
+            .collect();
+        let front = vec![vec![Glyph::empty(); width as usize]; height as usize];
         Self {
             width,
             height,
-            base_color,
-            /// TO DO Columns here
-            columns: vec![Column::new(height, base_color); width as usize],
+            columns,
+            front,
         }
     }
 
-    fn render<W: Write>(&self, out: &mut W) -> Result<()> {
-        queue!(out, cursor::Hide);
-        queue!(out, cursor::MoveTo(0, 0));
-        for y in 0..self.height {
-            for column in &self.columns {
-                column.render(out, y)?;
+    fn render<W: Write>(&mut self, out: &mut W) -> Result<()> {
+        queue!(out, cursor::Hide)?;
+        // the background is always black, so set it once per frame rather than
+        // per cell.
+        queue!(
+            out,
+            style::SetBackgroundColor(style::Color::Rgb { r: 0, g: 0, b: 0 })
+        )?;
+
+        for y in 0..self.height as usize {
+            let mut x = 0;
+            while x < self.width as usize {
+                let cur = &self.columns[x].glyphs[y];
+                if *cur == self.front[y][x] {
+                    x += 1;
+                    continue;
+                }
+
+                // start a run at the first dirty cell and extend it over
+                // consecutive dirty cells sharing the same foreground color.
+                let run_color = cur.color;
+                queue!(out, cursor::MoveTo(x as u16, y as u16))?;
+                queue!(
+                    out,
+                    style::SetForegroundColor(style::Color::Rgb {
+                        r: run_color.r,
+                        g: run_color.g,
+                        b: run_color.b
+                    })
+                )?;
+
+                while x < self.width as usize {
+                    let cell = self.columns[x].glyphs[y].clone();
+                    if cell == self.front[y][x] || cell.color != run_color {
+                        break;
+                    }
+                    queue!(out, style::Print(cell.character.to_string()))
+                        .context("write glyph to unicode chars")?;
+                    self.front[y][x] = cell;
+                    x += 1;
+                }
             }
         }
+
         queue!(out, style::ResetColor)?;
-        // queue!(out, cursor::Show)?;
         out.flush().context("flush output")?;
         Ok(())
     }
 
-    fn step<R: Rng>(&mut self, rand: &mut R) {
+    fn step<R: Rng>(&mut self, rand: &mut R, config: &Config, scheme: &ColorScheme) {
         for column in &mut self.columns {
-            column.step(rand);
+            column.step(rand, config, scheme);
         }
     }
 }
 
-fn choose_random<T: Clone>(a: T, b: T) -> T {
-    let mut rng = rand::thread_rng();
-    if rng.gen_bool(0.5) { a } else { b }
-}
-
 fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let mut config = Config::load()?;
+    cli.apply(&mut config);
+
+    let mut scheme = ColorScheme::resolve(&config)?;
+
     let (width, height) = terminal::size().context("determine teminal size")?;
-    /// default matrix green color hex code
-    let base_color = HexColor::rgb(0, 150, 255);
-    let base_color2 = HexColor::rgb(0, 255, 43);
-    let chosen = choose_random(base_color, base_color2);
 
-    let mut waterfall = MatrixWaterFall::new(width, height, chosen);
+    let seed = cli.seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("time to have passed since unix epoch")
+            .as_micros() as u64
+    });
+    let mut rand = Xoshiro256PlusPlus::seed_from_u64(seed);
+
+    let mut waterfall = MatrixWaterFall::new(width, height, &scheme, &mut rand);
     let mut stdout = std::io::stdout();
 
-    let seed = SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .expect("time to have passed since unix epoch")
-        .as_micros() as u64;
-    let mut rand = Xoshiro256PlusPlus::seed_from_u64(seed);
+    // switch to the alternate screen so the user's scrollback is preserved
+    terminal::enable_raw_mode().context("enable raw mode")?;
+    execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)
+        .context("enter alternate screen")?;
+
+    let result = run(&mut stdout, &mut waterfall, &mut config, &mut scheme, &mut rand);
+
+    // always restore the terminal, even if the loop errored out
+    teardown(&mut stdout).context("restore terminal")?;
+    result
+}
+
+/// Main animation loop with non-blocking keyboard handling. Returns when the
+/// user quits.
+fn run<W: Write, R: Rng>(
+    stdout: &mut W,
+    waterfall: &mut MatrixWaterFall,
+    config: &mut Config,
+    scheme: &mut ColorScheme,
+    rand: &mut R,
+) -> Result<()> {
+    let mut paused = false;
 
     loop {
-        waterfall.render(&mut stdout)?;
-        waterfall.step(&mut rand);
-        std::thread::sleep(Duration::from_millis(80));
+        // drain any pending input without blocking the animation
+        while event::poll(Duration::from_millis(0)).context("poll for input")? {
+            match event::read().context("read input event")? {
+                Event::Key(key) => match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Char(' ') => paused = !paused,
+                    KeyCode::Char('+') | KeyCode::Char('=') => {
+                        config.frame_delay_ms = config.frame_delay_ms.saturating_add(10);
+                    }
+                    KeyCode::Char('-') | KeyCode::Char('_') => {
+                        config.frame_delay_ms = config.frame_delay_ms.saturating_sub(10).max(10);
+                    }
+                    KeyCode::Char('c') => {
+                        cycle_scheme(config);
+                        *scheme = ColorScheme::resolve(config)?;
+                        *waterfall =
+                            MatrixWaterFall::new(waterfall.width, waterfall.height, scheme, rand);
+                        clear_screen(stdout)?;
+                    }
+                    _ => {}
+                },
+                Event::Resize(width, height) => {
+                    *waterfall = MatrixWaterFall::new(width, height, scheme, rand);
+                    clear_screen(stdout)?;
+                }
+                _ => {}
+            }
+        }
+
+        waterfall.render(stdout)?;
+        if !paused {
+            waterfall.step(rand, config, scheme);
+        }
+        std::thread::sleep(Duration::from_millis(config.frame_delay_ms));
     }
 }
+
+/// Advance `config.scheme` to the next built-in color scheme, wrapping around.
+fn cycle_scheme(config: &mut Config) {
+    let names = ColorScheme::names();
+    let current = names.iter().position(|n| *n == config.scheme);
+    let next = match current {
+        Some(i) => (i + 1) % names.len(),
+        None => 0,
+    };
+    config.scheme = names[next].to_string();
+}
+
+/// Wipe the physical screen after a rebuild so it matches the fresh empty
+/// `front` buffer; otherwise the diffing renderer leaves the previous frame
+/// frozen under the new grid.
+fn clear_screen<W: Write>(stdout: &mut W) -> Result<()> {
+    execute!(stdout, terminal::Clear(terminal::ClearType::All)).context("clear screen")?;
+    Ok(())
+}
+
+/// Restore the terminal to the state it was in before startup.
+fn teardown<W: Write>(stdout: &mut W) -> Result<()> {
+    terminal::disable_raw_mode()?;
+    execute!(
+        stdout,
+        style::ResetColor,
+        cursor::Show,
+        terminal::LeaveAlternateScreen
+    )?;
+    Ok(())
+}